@@ -3,7 +3,7 @@ use std::iter::successors;
 use hir::db::HirDatabase;
 use ra_syntax::{
     ast::{self, AstNode},
-    Direction, TextUnit,
+    Direction, SyntaxKind, TextUnit,
 };
 
 use crate::{Assist, AssistCtx, AssistId, TextRange};
@@ -34,11 +34,8 @@ use crate::{Assist, AssistCtx, AssistId, TextRange};
 // ```
 pub(crate) fn merge_match_arms(ctx: AssistCtx<impl HirDatabase>) -> Option<Assist> {
     let current_arm = ctx.find_node_at_offset::<ast::MatchArm>()?;
-    // Don't try to handle arms with guards for now - can add support for this later
-    if current_arm.guard().is_some() {
-        return None;
-    }
     let current_expr = current_arm.expr()?;
+    let current_guard = current_arm.guard().map(|it| it.syntax().text().to_string());
     let current_text_range = current_arm.syntax().text_range();
 
     enum CursorPos {
@@ -49,29 +46,38 @@ pub(crate) fn merge_match_arms(ctx: AssistCtx<impl HirDatabase>) -> Option<Assis
     let cursor_pos = if current_expr.syntax().text_range().contains(cursor_pos) {
         CursorPos::InExpr(current_text_range.end() - cursor_pos)
     } else {
-        CursorPos::InPat(cursor_pos)
+        CursorPos::InPat(cursor_pos - current_text_range.start())
     };
 
-    // We check if the following match arms match this one. We could, but don't,
-    // compare to the previous match arm as well.
-    let arms_to_merge = successors(Some(current_arm), next_arm)
-        .take_while(|arm| {
-            if arm.guard().is_some() {
-                return false;
-            }
-            match arm.expr() {
-                Some(expr) => expr.syntax().text() == current_expr.syntax().text(),
-                None => false,
-            }
-        })
-        .collect::<Vec<_>>();
+    let is_match = |arm: &ast::MatchArm| -> bool {
+        if arm.guard().map(|it| it.syntax().text().to_string()) != current_guard {
+            return false;
+        }
+        match arm.expr() {
+            Some(expr) => eq_ignoring_trivia(&expr, &current_expr),
+            None => false,
+        }
+    };
+
+    // We scan both backwards and forwards from the cursor, so that a run of identical
+    // arms merges as a whole no matter where in the run the cursor happens to be.
+    let mut preceding_arms =
+        successors(prev_arm(&current_arm), prev_arm).take_while(is_match).collect::<Vec<_>>();
+    preceding_arms.reverse();
+    let following_arms = successors(next_arm(&current_arm), next_arm).take_while(is_match);
+
+    let mut arms_to_merge = preceding_arms;
+    let current_arm_index = arms_to_merge.len();
+    arms_to_merge.push(current_arm);
+    arms_to_merge.extend(following_arms);
 
     if arms_to_merge.len() <= 1 {
         return None;
     }
 
     ctx.add_assist(AssistId("merge_match_arms"), "Merge match arms", |edit| {
-        let pats = if arms_to_merge.iter().any(contains_placeholder) {
+        let is_placeholder = arms_to_merge.iter().any(contains_placeholder);
+        let pats = if is_placeholder {
             "_".into()
         } else {
             arms_to_merge
@@ -81,8 +87,26 @@ pub(crate) fn merge_match_arms(ctx: AssistCtx<impl HirDatabase>) -> Option<Assis
                 .collect::<Vec<String>>()
                 .join(" | ")
         };
+        // Length of the pattern text that precedes the cursor's original arm, needed to
+        // relocate an `InPat` cursor now that earlier arms may have been prepended.
+        let pats_before_current_len = if is_placeholder {
+            None
+        } else {
+            Some(
+                arms_to_merge[..current_arm_index]
+                    .iter()
+                    .flat_map(ast::MatchArm::pats)
+                    .map(|x| x.syntax().to_string().len() + " | ".len())
+                    .sum::<usize>(),
+            )
+        };
+
+        let guard = match &current_guard {
+            Some(guard) => format!(" {}", guard),
+            None => String::new(),
+        };
 
-        let arm = format!("{} => {}", pats, current_expr.syntax().text());
+        let arm = format!("{}{} => {}", pats, guard, current_expr.syntax().text());
 
         let start = arms_to_merge.first().unwrap().syntax().text_range().start();
         let end = arms_to_merge.last().unwrap().syntax().text_range().end();
@@ -90,7 +114,10 @@ pub(crate) fn merge_match_arms(ctx: AssistCtx<impl HirDatabase>) -> Option<Assis
         edit.target(current_text_range);
         edit.set_cursor(match cursor_pos {
             CursorPos::InExpr(back_offset) => start + TextUnit::from_usize(arm.len()) - back_offset,
-            CursorPos::InPat(offset) => offset,
+            CursorPos::InPat(offset) => match pats_before_current_len {
+                Some(len) => start + TextUnit::from_usize(len) + offset,
+                None => start,
+            },
         });
         edit.replace(TextRange::from_to(start, end), arm);
     })
@@ -107,6 +134,58 @@ fn next_arm(arm: &ast::MatchArm) -> Option<ast::MatchArm> {
     arm.syntax().siblings(Direction::Next).skip(1).find_map(ast::MatchArm::cast)
 }
 
+fn prev_arm(arm: &ast::MatchArm) -> Option<ast::MatchArm> {
+    arm.syntax().siblings(Direction::Prev).skip(1).find_map(ast::MatchArm::cast)
+}
+
+/// Peels off a block that only consists of a single tail expression, e.g. turns
+/// `{ { foo() } }` into `foo()`, so such blocks compare equal to the bare expression.
+fn unwrap_trivial_block(expr: ast::Expr) -> ast::Expr {
+    match &expr {
+        ast::Expr::BlockExpr(block_expr) => match block_expr.block() {
+            Some(block) if block.statements().next().is_none() => match block.expr() {
+                Some(tail) => unwrap_trivial_block(tail),
+                None => expr,
+            },
+            _ => expr,
+        },
+        _ => expr,
+    }
+}
+
+/// Compares two expressions ignoring whitespace and comments, and treating a trivial
+/// single-expression block as equivalent to the bare expression it contains.
+fn eq_ignoring_trivia(lhs: &ast::Expr, rhs: &ast::Expr) -> bool {
+    let lhs = unwrap_trivial_block(lhs.clone());
+    let rhs = unwrap_trivial_block(rhs.clone());
+
+    let is_trivia = |token: &ra_syntax::SyntaxToken| {
+        matches!(token.kind(), SyntaxKind::WHITESPACE | SyntaxKind::COMMENT)
+    };
+    let mut lhs_tokens = lhs
+        .syntax()
+        .descendants_with_tokens()
+        .filter_map(|it| it.into_token())
+        .filter(|it| !is_trivia(it));
+    let mut rhs_tokens = rhs
+        .syntax()
+        .descendants_with_tokens()
+        .filter_map(|it| it.into_token())
+        .filter(|it| !is_trivia(it));
+
+    loop {
+        match (lhs_tokens.next(), rhs_tokens.next()) {
+            (Some(lhs), Some(rhs)) => {
+                if lhs.kind() != rhs.kind() || lhs.text() != rhs.text() {
+                    return false;
+                }
+            }
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::merge_match_arms;
@@ -262,4 +341,107 @@ mod tests {
             "#,
         );
     }
+
+    #[test]
+    fn merge_match_arms_identical_guards() {
+        check_assist(
+            merge_match_arms,
+            r#"
+            #[derive(Debug)]
+            enum X {
+                A(i32),
+                B(i32),
+                C
+            }
+
+            fn main() {
+                let x = X::A(0);
+                let y = match x {
+                    X::A(a) if a > 5 => { <|>1i32 },
+                    X::B(a) if a > 5 => { 1i32 },
+                    X::C => { 2i32 }
+                }
+            }
+            "#,
+            r#"
+            #[derive(Debug)]
+            enum X {
+                A(i32),
+                B(i32),
+                C
+            }
+
+            fn main() {
+                let x = X::A(0);
+                let y = match x {
+                    X::A(a) | X::B(a) if a > 5 => { <|>1i32 },
+                    X::C => { 2i32 }
+                }
+            }
+            "#,
+        );
+    }
+
+    #[test]
+    fn merge_match_arms_allows_body_differences() {
+        check_assist(
+            merge_match_arms,
+            r#"
+            #[derive(Debug)]
+            enum X { A, B, C }
+
+            fn main() {
+                let x = X::A;
+                let y = match x {
+                    X::A => <|>{ 1i32 },
+                    X::B => 1i32,
+                    X::C => { 2i32 },
+                }
+            }
+            "#,
+            r#"
+            #[derive(Debug)]
+            enum X { A, B, C }
+
+            fn main() {
+                let x = X::A;
+                let y = match x {
+                    X::A | X::B => <|>{ 1i32 },
+                    X::C => { 2i32 },
+                }
+            }
+            "#,
+        );
+    }
+
+    #[test]
+    fn merges_both_preceding_and_following_arms() {
+        check_assist(
+            merge_match_arms,
+            r#"
+            enum X { A, B, C, D, E }
+
+            fn main() {
+                match X::A {
+                    X::A => 92,
+                    X::B<|> => 92,
+                    X::C => 92,
+                    X::D => 62,
+                    _ => panic!(),
+                }
+            }
+            "#,
+            r#"
+            enum X { A, B, C, D, E }
+
+            fn main() {
+                match X::A {
+                    X::A | X::B<|> | X::C => 92,
+                    X::D => 62,
+                    _ => panic!(),
+                }
+            }
+            "#,
+        )
+    }
 }
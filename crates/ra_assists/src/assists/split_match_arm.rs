@@ -0,0 +1,221 @@
+use hir::db::HirDatabase;
+use ra_syntax::{
+    ast::{self, AstNode},
+    SyntaxKind,
+};
+
+use crate::{Assist, AssistCtx, AssistId};
+
+// Assist: split_match_arm
+//
+// Splits an or-pattern match arm into separate arms.
+//
+// ```
+// enum X { A, B, C }
+//
+// fn main() {
+//     let x = X::A;
+//     let y = match x {
+//         X::A | X::B<|> => 1i32,
+//         X::C => 2i32,
+//     }
+// }
+// ```
+// ->
+// ```
+// enum X { A, B, C }
+//
+// fn main() {
+//     let x = X::A;
+//     let y = match x {
+//         X::A => 1i32,
+//         X::B => 1i32,
+//         X::C => 2i32,
+//     }
+// }
+// ```
+pub(crate) fn split_match_arm(ctx: AssistCtx<impl HirDatabase>) -> Option<Assist> {
+    let match_arm = ctx.find_node_at_offset::<ast::MatchArm>()?;
+
+    let pats = match_arm.pats().collect::<Vec<_>>();
+    // Don't bother if there is only one pattern, there is nothing to split. Likewise, a
+    // wildcard pattern is exhaustive on its own, so splitting it off would leave the
+    // other arms unreachable.
+    if pats.len() < 2 || pats.iter().any(is_placeholder) {
+        return None;
+    }
+
+    let current_text_range = match_arm.syntax().text_range();
+    let arm_expr = match_arm.expr()?;
+    let guard = match match_arm.guard() {
+        Some(guard) => format!(" {}", guard.syntax().text()),
+        None => String::new(),
+    };
+    let has_comma = match_arm
+        .syntax()
+        .last_token()
+        .map(|it| it.kind() == SyntaxKind::COMMA)
+        .unwrap_or(false);
+    let indent = match_arm_indent(&match_arm);
+
+    ctx.add_assist(AssistId("split_match_arm"), "Split match arm", |edit| {
+        let mut new_arm_list = pats
+            .iter()
+            .map(|pat| {
+                format!("{}{} => {}", pat.syntax().text(), guard, arm_expr.syntax().text())
+            })
+            .collect::<Vec<String>>()
+            .join(&format!(",\n{}", indent));
+        if has_comma {
+            new_arm_list.push(',');
+        }
+
+        edit.target(current_text_range);
+        edit.set_cursor(current_text_range.start());
+        edit.replace(current_text_range, new_arm_list);
+    })
+}
+
+fn is_placeholder(pat: &ast::Pat) -> bool {
+    match pat {
+        ast::Pat::PlaceholderPat(..) => true,
+        _ => false,
+    }
+}
+
+fn match_arm_indent(arm: &ast::MatchArm) -> String {
+    arm.syntax()
+        .prev_sibling_or_token()
+        .and_then(|it| it.into_token())
+        .filter(|it| it.kind() == SyntaxKind::WHITESPACE)
+        .map(|it| it.text().rsplit('\n').next().unwrap_or("").to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split_match_arm;
+    use crate::helpers::{check_assist, check_assist_not_applicable};
+
+    #[test]
+    fn split_match_arm_works() {
+        check_assist(
+            split_match_arm,
+            r#"
+            #[derive(Debug)]
+            enum X { A, B, C }
+
+            fn main() {
+                let x = X::A;
+                let y = match x {
+                    X::A | X::B<|> => { 1i32 },
+                    X::C => { 2i32 },
+                }
+            }
+            "#,
+            r#"
+            #[derive(Debug)]
+            enum X { A, B, C }
+
+            fn main() {
+                let x = X::A;
+                let y = match x {
+                    <|>X::A => { 1i32 },
+                    X::B => { 1i32 },
+                    X::C => { 2i32 },
+                }
+            }
+            "#,
+        );
+    }
+
+    #[test]
+    fn split_match_arm_keeps_guard() {
+        check_assist(
+            split_match_arm,
+            r#"
+            #[derive(Debug)]
+            enum X { A(i32), B(i32), C }
+
+            fn main() {
+                let x = X::A(0);
+                let y = match x {
+                    X::A(a) | X::B(a)<|> if a > 5 => { 1i32 },
+                    X::C => { 2i32 },
+                }
+            }
+            "#,
+            r#"
+            #[derive(Debug)]
+            enum X { A(i32), B(i32), C }
+
+            fn main() {
+                let x = X::A(0);
+                let y = match x {
+                    <|>X::A(a) if a > 5 => { 1i32 },
+                    X::B(a) if a > 5 => { 1i32 },
+                    X::C => { 2i32 },
+                }
+            }
+            "#,
+        );
+    }
+
+    #[test]
+    fn split_match_arm_rejects_single_pattern() {
+        check_assist_not_applicable(
+            split_match_arm,
+            r#"
+            #[derive(Debug)]
+            enum X { A, B, C }
+
+            fn main() {
+                let x = X::A;
+                let y = match x {
+                    X::A<|> => { 1i32 },
+                    X::B => { 1i32 },
+                    X::C => { 2i32 },
+                }
+            }
+            "#,
+        );
+    }
+
+    #[test]
+    fn split_match_arm_rejects_wildcard() {
+        check_assist_not_applicable(
+            split_match_arm,
+            r#"
+            #[derive(Debug)]
+            enum X { A, B, C }
+
+            fn main() {
+                let x = X::A;
+                let y = match x {
+                    X::A => { 1i32 },
+                    _<|> => { 2i32 },
+                }
+            }
+            "#,
+        );
+    }
+
+    #[test]
+    fn split_match_arm_rejects_wildcard_in_or_pattern() {
+        check_assist_not_applicable(
+            split_match_arm,
+            r#"
+            #[derive(Debug)]
+            enum X { A, B, C }
+
+            fn main() {
+                let x = X::A;
+                let y = match x {
+                    X::A => { 1i32 },
+                    _<|> | X::B => { 2i32 },
+                }
+            }
+            "#,
+        );
+    }
+}